@@ -0,0 +1,52 @@
+use std::path::Path;
+use std::time::Duration;
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+
+use crate::AppError;
+
+/// Window over which bursts of filesystem events are collapsed into a single
+/// `on_change` invocation. Editors typically emit several events per save, so
+/// coalescing avoids a storm of reload calls.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Watch `dirs` recursively and invoke `on_change` once per debounced burst of
+/// filesystem events.
+///
+/// Raw `notify` events are routed through a `tokio::sync::mpsc` channel and
+/// coalesced within [`DEBOUNCE`], so a single save that produces many events
+/// results in exactly one `on_change` call. The returned [`RecommendedWatcher`]
+/// must be kept alive by the caller for as long as watching should continue.
+pub fn watch<P, F>(dirs: &[P], on_change: F) -> Result<RecommendedWatcher, AppError>
+where
+    P: AsRef<Path>,
+    F: Fn() + Send + 'static,
+{
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |_event| {
+        // A closed receiver just means the spawned task has gone away.
+        let _ = tx.send(());
+    })?;
+
+    for dir in dirs {
+        watcher.watch(dir.as_ref(), RecursiveMode::Recursive)?;
+    }
+
+    tokio::spawn(async move {
+        while rx.recv().await.is_some() {
+            // Drain any further events that arrive within the debounce window
+            // so the whole burst collapses into one `on_change`.
+            loop {
+                match tokio::time::timeout(DEBOUNCE, rx.recv()).await {
+                    Ok(Some(())) => continue,
+                    Ok(None) => return,
+                    Err(_) => break,
+                }
+            }
+            on_change();
+        }
+    });
+
+    Ok(watcher)
+}
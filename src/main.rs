@@ -1,6 +1,7 @@
 use askama::Template;
 use axum::{
     extract::Request,
+    extract::State,
     http::HeaderMap,
     http::StatusCode,
     response::{Html, IntoResponse, Response},
@@ -8,19 +9,60 @@ use axum::{
     Router,
 };
 use bytes::Bytes;
-use notify::Watcher;
-use std::path::Path;
+use minijinja::{context, Environment};
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use tower_http::classify::ServerErrorsFailureClass;
 use tower_http::services::ServeDir;
 use tower_http::trace::TraceLayer;
+use tower_livereload::predicate::Predicate;
 use tower_livereload::LiveReloadLayer;
 use tracing::info;
 use tracing::Span;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+mod file_watcher;
+
+/// Application-wide error type.
+///
+/// Collects the failure sources that can occur while booting the server and
+/// while rendering responses into a single type, so that both `main` and the
+/// request handlers have one place to classify and surface them.
+#[derive(thiserror::Error, Debug)]
+enum AppError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("file watcher error: {0}")]
+    Watch(#[from] notify::Error),
+    #[error("failed to render template: {0}")]
+    Render(askama::Error),
+    #[error("missing environment variable: {var}")]
+    MissingEnvVar { var: &'static str },
+    #[error("runtime template error: {0}")]
+    Template(#[from] minijinja::Error),
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        match self {
+            AppError::Render(err) => {
+                tracing::error!("failed to render template: {}", err);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+            AppError::Template(err) => {
+                tracing::error!("failed to render runtime template: {}", err);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+            other => {
+                tracing::error!("unhandled application error: {}", other);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
+
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
+async fn main() -> Result<(), AppError> {
     std::env::set_var("RUST_LOG", "debug");
     tracing_subscriber::registry()
         .with(
@@ -32,8 +74,28 @@ async fn main() -> anyhow::Result<()> {
 
     info!("Initializing router...");
 
-    let assets_path = std::env::current_dir().unwrap();
-    let live_reload = LiveReloadLayer::new();
+    if is_dev() {
+        // Tailwind is an optional dev convenience: a missing binary or an
+        // unconfigured input stylesheet should not stop the server from
+        // booting, so log the failure and carry on.
+        if let Err(err) = spawn_tailwind_watcher() {
+            tracing::warn!("Tailwind watcher not started: {}", err);
+        }
+    }
+
+    // In development, load templates from disk for runtime hot-reload; in
+    // production the compiled askama templates are used instead.
+    let templates: AppState = if is_dev() {
+        Some(Templates::load()?)
+    } else {
+        None
+    };
+
+    let assets_path = std::env::current_dir()?;
+    // htmx swaps target a fragment, so injecting the live-reload shim into
+    // those responses would corrupt the swapped markup. Skip injection for
+    // requests carrying the `HX-Request` header and only shim full-page loads.
+    let live_reload = LiveReloadLayer::new().request_predicate(NotHtmxPredicate);
     let reloader = live_reload.reloader();
     let router = Router::new()
         .route("/", get(home))
@@ -65,36 +127,187 @@ async fn main() -> anyhow::Result<()> {
                     },
                 ),
         )
-        .layer(live_reload);
+        .layer(live_reload)
+        .with_state(templates.clone());
 
     // handling live reloading
-    let mut watcher = notify::recommended_watcher(move |_| reloader.reload())?;
     let watcher_template_path_str = format!("{}/templates", assets_path.to_str().unwrap());
-    let watcher_template_path = Path::new(watcher_template_path_str.as_str());
     let watcher_assets_path_str = format!("{}/assets", assets_path.to_str().unwrap());
-    let watcher_assets_path = Path::new(watcher_assets_path_str.as_str());
-    watcher.watch(watcher_template_path, notify::RecursiveMode::Recursive)?;
-    watcher.watch(watcher_assets_path, notify::RecursiveMode::Recursive)?;
 
-    let port = 8080_u16;
-    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
+    // Watch `templates/` separately so the runtime minijinja environment is
+    // only re-parsed on template edits — asset/CSS writes (including Tailwind's
+    // own `assets/style.css` output) should trigger a browser reload but never
+    // a template rebuild.
+    let templates_for_watch = templates.clone();
+    let template_reloader = reloader.clone();
+    let _template_watcher = file_watcher::watch(&[watcher_template_path_str], move || {
+        if let Some(templates) = &templates_for_watch {
+            templates.reload();
+        }
+        template_reloader.reload();
+    })?;
+
+    // Keep both watcher handles alive for the process lifetime; dropping one
+    // stops its events.
+    let _asset_watcher = file_watcher::watch(&[watcher_assets_path_str], move || {
+        reloader.reload();
+    })?;
+
+    // Prefer a socket inherited from `systemfd`/`listenfd` so restarts under
+    // `cargo watch` keep the port and live connections; otherwise bind afresh.
+    let listener = match listenfd::ListenFd::from_env().take_tcp_listener(0)? {
+        Some(listener) => {
+            listener.set_nonblocking(true)?;
+            info!("Router initialized, serving on inherited socket");
+            tokio::net::TcpListener::from_std(listener)?
+        }
+        None => {
+            let port = 8080_u16;
+            let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
+            info!("Router initialized, now listening on port {}", port);
+            tokio::net::TcpListener::bind(addr).await?
+        }
+    };
+
+    axum::serve(listener, router).await?;
+
+    Ok(())
+}
+
+/// Shared handler state: the runtime template store in dev, `None` in prod.
+///
+/// When present, handlers render through the reloadable minijinja environment;
+/// when absent they fall back to the build-time askama templates.
+type AppState = Option<Templates>;
+
+/// A reloadable store of runtime templates backed by minijinja.
+///
+/// Holds the environment behind an `Arc<RwLock<_>>` so the `notify` watcher can
+/// swap in a freshly parsed environment in place while handlers keep rendering
+/// against whatever is currently loaded.
+///
+/// NOTE: this dev-only path renders the same `templates/*.html` files through
+/// minijinja, whereas the production path renders them through askama. The two
+/// engines are *not* fully interchangeable — askama is not strictly
+/// Jinja2-compatible (its filter set, `match`/`let`, whitespace control and
+/// loop variables differ), so any askama-specific construct can render
+/// differently here or fail at runtime. The hot-reload preview is therefore
+/// only faithful as long as the starter's templates stay pure Jinja2; the ones
+/// shipped with this starter are plain interpolation-free markup, which both
+/// engines render identically.
+#[derive(Clone)]
+struct Templates {
+    env: Arc<RwLock<Environment<'static>>>,
+}
+
+impl Templates {
+    /// Load every `templates/*.html` file from disk into a new store.
+    fn load() -> Result<Self, AppError> {
+        Ok(Self {
+            env: Arc::new(RwLock::new(build_env()?)),
+        })
+    }
+
+    /// Rebuild the environment from disk in place, leaving the last good
+    /// environment loaded if the reload fails.
+    fn reload(&self) {
+        match build_env() {
+            Ok(env) => *self.env.write().unwrap() = env,
+            Err(err) => tracing::error!("failed to reload templates: {}", err),
+        }
+    }
+
+    /// Render the named template against an empty context.
+    fn render(&self, name: &str) -> Result<String, AppError> {
+        let env = self.env.read().unwrap();
+        let template = env.get_template(name)?;
+        Ok(template.render(context! {})?)
+    }
+}
+
+/// Build a minijinja environment from the `templates/` directory.
+fn build_env() -> Result<Environment<'static>, AppError> {
+    let mut env = Environment::new();
+    for entry in std::fs::read_dir("templates")? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) == Some("html") {
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            env.add_template_owned(name, std::fs::read_to_string(&path)?)?;
+        }
+    }
+    Ok(env)
+}
+
+/// Read a required environment variable, mapping an unset value to
+/// [`AppError::MissingEnvVar`] so callers can surface it like any other
+/// application error.
+fn required_env(var: &'static str) -> Result<String, AppError> {
+    std::env::var(var).map_err(|_| AppError::MissingEnvVar { var })
+}
+
+/// Returns `true` when the process is running in a development environment.
+///
+/// Treats either `DEV` being set to a truthy value or `APP_ENV=development`
+/// as a development run; anything else is considered production.
+fn is_dev() -> bool {
+    matches!(
+        std::env::var("DEV").as_deref(),
+        Ok("1") | Ok("true") | Ok("TRUE")
+    ) || std::env::var("APP_ENV").as_deref() == Ok("development")
+}
+
+/// Spawn the Tailwind CLI in watch mode as a background task.
+///
+/// By default the generated stylesheet lands under `assets/`, which the `notify`
+/// watcher already observes, so Tailwind edits flow through to a browser
+/// reload; an output path set outside `assets/` is not watched. The
+/// input stylesheet is taken from the required `TAILWIND_INPUT` variable (there
+/// is no universally-correct default entry point), while the output path falls
+/// back to the conventional `assets/style.css`. Failing to spawn the process is
+/// surfaced as an [`AppError`] instead of silently dying.
+fn spawn_tailwind_watcher() -> Result<(), AppError> {
+    let input = required_env("TAILWIND_INPUT")?;
+    let output =
+        std::env::var("TAILWIND_OUTPUT").unwrap_or_else(|_| "assets/style.css".to_string());
 
-    info!("Router initialized, now listening on port {}", port);
+    info!("Starting Tailwind watcher ({} -> {})", input, output);
 
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, router).await.unwrap();
+    let child = tokio::process::Command::new("tailwindcss")
+        .args(["-i", &input, "-o", &output, "--watch"])
+        .spawn()?;
+
+    tokio::spawn(async move {
+        let mut child = child;
+        match child.wait().await {
+            Ok(status) => tracing::info!("Tailwind watcher exited with {}", status),
+            Err(err) => tracing::error!("Tailwind watcher failed: {}", err),
+        }
+    });
 
     Ok(())
 }
 
-async fn home() -> impl IntoResponse {
-    let template = HomeTemplate {};
-    HtmlTemplate(template)
+async fn home(State(templates): State<AppState>) -> Response {
+    match &templates {
+        Some(templates) => render_runtime(templates, "home.html"),
+        None => HtmlTemplate(HomeTemplate {}).into_response(),
+    }
 }
 
-async fn another_page() -> impl IntoResponse {
-    let template = AnotherPageTemplate {};
-    HtmlTemplate(template)
+async fn another_page(State(templates): State<AppState>) -> Response {
+    match &templates {
+        Some(templates) => render_runtime(templates, "another-page.html"),
+        None => HtmlTemplate(AnotherPageTemplate {}).into_response(),
+    }
+}
+
+/// Render a template through the runtime store, turning failures into an
+/// [`AppError`] response.
+fn render_runtime(templates: &Templates, name: &str) -> Response {
+    match templates.render(name) {
+        Ok(html) => Html(html).into_response(),
+        Err(err) => err.into_response(),
+    }
 }
 
 #[derive(Template)]
@@ -105,6 +318,19 @@ struct HomeTemplate;
 #[template(path = "another-page.html")]
 struct AnotherPageTemplate;
 
+/// Live-reload request predicate that excludes htmx-driven requests.
+///
+/// Returns `false` whenever the incoming request carries the `HX-Request`
+/// header so that `tower-livereload` leaves htmx partials untouched.
+#[derive(Copy, Clone)]
+struct NotHtmxPredicate;
+
+impl<T> Predicate<Request<T>> for NotHtmxPredicate {
+    fn check(&mut self, request: &Request<T>) -> bool {
+        !request.headers().contains_key("HX-Request")
+    }
+}
+
 struct HtmlTemplate<T>(T);
 
 impl<T> IntoResponse for HtmlTemplate<T>
@@ -114,11 +340,7 @@ where
     fn into_response(self) -> Response {
         match self.0.render() {
             Ok(html) => Html(html).into_response(),
-            Err(err) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to render template. Error: {}", err),
-            )
-                .into_response(),
+            Err(err) => AppError::Render(err).into_response(),
         }
     }
 }